@@ -1,5 +1,11 @@
 use std::sync::Mutex;
-use tauri::State;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+use crate::sidecar::{SidecarLifecycleService, SidecarStatus};
+
+// How often wait_for_sidecar re-checks the lifecycle status.
+const WAIT_POLL_INTERVAL: Duration = Duration::from_millis(100);
 
 pub struct SidecarState {
     pub port: Option<u16>,
@@ -12,13 +18,61 @@ pub fn get_sidecar_port(state: State<'_, Mutex<SidecarState>>) -> Result<u16, St
 }
 
 #[tauri::command]
-pub fn kill_sidecar(
-    child_state: State<'_, Mutex<Option<tauri_plugin_shell::process::CommandChild>>>,
-) -> Result<(), String> {
-    let mut guard = child_state.lock().map_err(|e| e.to_string())?;
-    if let Some(child) = guard.take() {
-        child.kill().map_err(|e| e.to_string())?;
-        eprintln!("Sidecar killed for update");
+pub fn start_sidecar(
+    app_handle: AppHandle,
+    lifecycle: State<'_, Mutex<SidecarLifecycleService>>,
+) -> Result<String, String> {
+    let mut lifecycle = lifecycle.lock().map_err(|e| e.to_string())?;
+    lifecycle.start(&app_handle)?;
+    Ok("Sidecar started".to_string())
+}
+
+#[tauri::command]
+pub fn stop_sidecar(
+    app_handle: AppHandle,
+    lifecycle: State<'_, Mutex<SidecarLifecycleService>>,
+) -> Result<String, String> {
+    let mut lifecycle = lifecycle.lock().map_err(|e| e.to_string())?;
+    lifecycle.stop(&app_handle)?;
+    Ok("Sidecar stopped".to_string())
+}
+
+#[tauri::command]
+pub fn restart_sidecar(
+    app_handle: AppHandle,
+    lifecycle: State<'_, Mutex<SidecarLifecycleService>>,
+) -> Result<String, String> {
+    let mut lifecycle = lifecycle.lock().map_err(|e| e.to_string())?;
+    lifecycle.restart(&app_handle)?;
+    Ok("Sidecar restarted".to_string())
+}
+
+// Resolves once the sidecar reaches Running, or rejects with a descriptive
+// error if it crashes or doesn't become ready within timeout_ms.
+#[tauri::command]
+pub async fn wait_for_sidecar(
+    app_handle: AppHandle,
+    timeout_ms: u64,
+) -> Result<String, String> {
+    let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+
+    loop {
+        {
+            let lifecycle = app_handle.state::<Mutex<SidecarLifecycleService>>();
+            let lifecycle = lifecycle.lock().map_err(|e| e.to_string())?;
+            match lifecycle.status() {
+                SidecarStatus::Running => return Ok("Sidecar ready".to_string()),
+                SidecarStatus::Crashed => return Err(lifecycle.last_error_message()),
+                SidecarStatus::Stopped | SidecarStatus::Starting => {}
+            }
+        }
+
+        if Instant::now() >= deadline {
+            return Err(format!(
+                "Timed out after {timeout_ms}ms waiting for sidecar to become ready"
+            ));
+        }
+
+        tokio::time::sleep(WAIT_POLL_INTERVAL).await;
     }
-    Ok(())
 }
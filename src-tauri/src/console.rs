@@ -0,0 +1,72 @@
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+use crate::sidecar::SidecarStatus;
+
+// Emitted to the main window for each line of sidecar output.
+pub const SIDECAR_LOG_EVENT: &str = "sidecar-log";
+
+// Emitted to the main window on lifecycle status changes.
+pub const SIDECAR_STATUS_EVENT: &str = "sidecar-status";
+
+/// Which sidecar pipe a `ConsoleEvent` line came from.
+#[derive(Debug, Clone, Copy, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConsoleStream {
+    Stdout,
+    Stderr,
+}
+
+// A single line of sidecar output, forwarded to the frontend.
+#[derive(Debug, Clone, Serialize)]
+pub struct ConsoleEvent {
+    pub stream: ConsoleStream,
+    pub message: String,
+    pub timestamp: u64,
+}
+
+impl ConsoleEvent {
+    pub fn new(stream: ConsoleStream, message: String) -> Self {
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or(0);
+
+        Self {
+            stream,
+            message,
+            timestamp,
+        }
+    }
+}
+
+pub fn emit_console_event(app_handle: &AppHandle, stream: ConsoleStream, message: String) {
+    let event = ConsoleEvent::new(stream, message);
+    if let Err(e) = app_handle.emit(SIDECAR_LOG_EVENT, event) {
+        eprintln!("Failed to emit sidecar-log event: {e}");
+    }
+}
+
+// A lifecycle status change, e.g. a crash or an auto-restart attempt.
+#[derive(Debug, Clone, Serialize)]
+pub struct StatusEvent {
+    pub status: SidecarStatus,
+    pub attempt: u32,
+    pub message: String,
+}
+
+pub fn emit_status_event(
+    app_handle: &AppHandle,
+    status: SidecarStatus,
+    attempt: u32,
+    message: String,
+) {
+    let event = StatusEvent {
+        status,
+        attempt,
+        message,
+    };
+    if let Err(e) = app_handle.emit(SIDECAR_STATUS_EVENT, event) {
+        eprintln!("Failed to emit sidecar-status event: {e}");
+    }
+}
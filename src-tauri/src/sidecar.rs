@@ -0,0 +1,474 @@
+use std::collections::VecDeque;
+use std::net::{SocketAddr, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+use tauri_plugin_shell::process::{CommandChild, CommandEvent};
+use tauri_plugin_shell::ShellExt;
+
+use crate::commands::SidecarState;
+use crate::console::{emit_console_event, emit_status_event, ConsoleStream};
+
+// Respawn backoff: each unexpected exit starts a new restart sequence at
+// INITIAL_BACKOFF, doubled after each failed respawn attempt within that
+// sequence. It is not carried over from one exit to the next.
+const INITIAL_BACKOFF: Duration = Duration::from_millis(250);
+const MAX_BACKOFF: Duration = Duration::from_secs(8);
+
+fn next_backoff(current: Duration) -> Duration {
+    std::cmp::min(current * 2, MAX_BACKOFF)
+}
+
+// How long to wait for the health endpoint after the `PORT:` line is seen.
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(15);
+const HEALTH_CHECK_POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+// Trailing stderr lines kept around to surface in readiness errors.
+const STDERR_HISTORY_CAP: usize = 20;
+
+// Written to the sidecar's stdin to request a clean shutdown before
+// falling back to kill().
+const SHUTDOWN_SENTINEL: &[u8] = b"SHUTDOWN\n";
+const SHUTDOWN_GRACE_PERIOD: Duration = Duration::from_secs(3);
+
+/// Lifecycle state of the managed Python sidecar process.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SidecarStatus {
+    Stopped,
+    Starting,
+    Running,
+    Crashed,
+}
+
+// Spawned via tauri_plugin_shell as a bundled binary rather than a
+// hardcoded .venv path, so this also works from a distributed bundle.
+pub struct SidecarLifecycleService {
+    child: Option<CommandChild>,
+    port: Option<u16>,
+    status: SidecarStatus,
+    // Set just before an app-initiated stop() kills the child, so the
+    // event loop can skip the auto-restart for a deliberate shutdown.
+    manually_killed: Arc<AtomicBool>,
+    // Trailing stderr lines from the current/last run, newest last.
+    stderr_history: Arc<Mutex<VecDeque<String>>>,
+    // Reason the sidecar last failed to become ready.
+    last_error: Arc<Mutex<Option<String>>>,
+    // Set by the event loop once Terminated fires, so graceful_shutdown
+    // can skip the kill() fallback.
+    has_exited: Arc<AtomicBool>,
+}
+
+impl SidecarLifecycleService {
+    pub fn new() -> Self {
+        Self {
+            child: None,
+            port: None,
+            status: SidecarStatus::Stopped,
+            manually_killed: Arc::new(AtomicBool::new(false)),
+            stderr_history: Arc::new(Mutex::new(VecDeque::with_capacity(STDERR_HISTORY_CAP))),
+            last_error: Arc::new(Mutex::new(None)),
+            has_exited: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    pub fn status(&self) -> SidecarStatus {
+        self.status
+    }
+
+    /// Combines the last recorded readiness failure with the tail of
+    /// captured stderr output.
+    pub fn last_error_message(&self) -> String {
+        let reason = self
+            .last_error
+            .lock()
+            .unwrap()
+            .clone()
+            .unwrap_or_else(|| "Sidecar is not ready".to_string());
+
+        let stderr_tail: Vec<String> = self.stderr_history.lock().unwrap().iter().cloned().collect();
+        if stderr_tail.is_empty() {
+            reason
+        } else {
+            format!("{reason}\nLast sidecar output:\n{}", stderr_tail.join("\n"))
+        }
+    }
+
+    // Resolves the bundled sidecar binary and spawns it, then forwards its
+    // stdout/stderr as structured log events and watches for the `PORT:`
+    // line and process termination.
+    pub fn start(&mut self, app_handle: &AppHandle) -> Result<(), String> {
+        if self.child.is_some() {
+            return Err("Sidecar already running".to_string());
+        }
+
+        self.status = SidecarStatus::Starting;
+        self.port = None;
+
+        // Fresh Arcs per generation, not a reset in place: the old
+        // event-loop task below still holds the old Arcs, so a late
+        // Terminated for a killed-but-not-yet-reaped child reads its own
+        // generation's state instead of racing this one.
+        self.manually_killed = Arc::new(AtomicBool::new(false));
+        self.has_exited = Arc::new(AtomicBool::new(false));
+        self.stderr_history = Arc::new(Mutex::new(VecDeque::with_capacity(STDERR_HISTORY_CAP)));
+        self.last_error = Arc::new(Mutex::new(None));
+
+        let sidecar_command = app_handle
+            .shell()
+            .sidecar("main")
+            .map_err(|e| format!("Failed to resolve sidecar binary: {e}"))?;
+
+        let (mut rx, child) = sidecar_command
+            .spawn()
+            .map_err(|e| format!("Failed to spawn sidecar: {e}"))?;
+
+        tauri::async_runtime::spawn({
+            let handle = app_handle.clone();
+            let manually_killed = self.manually_killed.clone();
+            let stderr_history = self.stderr_history.clone();
+            let last_error = self.last_error.clone();
+            let has_exited = self.has_exited.clone();
+            async move {
+                let mut became_ready = false;
+
+                'event_loop: while let Some(event) = rx.recv().await {
+                    match event {
+                        CommandEvent::Stdout(bytes) => {
+                            let line = Self::log_line(&handle, ConsoleStream::Stdout, &bytes);
+
+                            let Some(port_str) = line.strip_prefix("PORT:") else {
+                                continue;
+                            };
+
+                            let port = match port_str.parse::<u16>() {
+                                Ok(port) => port,
+                                Err(_) => {
+                                    *last_error.lock().unwrap() = Some(format!(
+                                        "Failed to parse port from sidecar output: {port_str:?}"
+                                    ));
+                                    let lifecycle = handle.state::<Mutex<SidecarLifecycleService>>();
+                                    lifecycle.lock().unwrap().status = SidecarStatus::Crashed;
+                                    continue;
+                                }
+                            };
+
+                            eprintln!("Sidecar reported port {port}, waiting for health check");
+                            {
+                                let lifecycle = handle.state::<Mutex<SidecarLifecycleService>>();
+                                lifecycle.lock().unwrap().port = Some(port);
+                            }
+
+                            // Race the probe against the event stream so a
+                            // process exit mid-check is seen immediately
+                            // instead of sitting unread in the channel.
+                            let probe = tauri::async_runtime::spawn_blocking(move || {
+                                Self::probe_health(port, HEALTH_CHECK_TIMEOUT)
+                            });
+                            tokio::pin!(probe);
+
+                            loop {
+                                tokio::select! {
+                                    probe_result = &mut probe => {
+                                        let result = probe_result
+                                            .unwrap_or_else(|e| Err(format!("Health check task panicked: {e}")));
+                                        became_ready = Self::finish_health_check(&handle, port, result);
+                                        break;
+                                    }
+                                    next_event = rx.recv() => {
+                                        match next_event {
+                                            Some(CommandEvent::Stdout(bytes)) => {
+                                                Self::log_line(&handle, ConsoleStream::Stdout, &bytes);
+                                            }
+                                            Some(CommandEvent::Stderr(bytes)) => {
+                                                let line = Self::log_line(&handle, ConsoleStream::Stderr, &bytes);
+                                                Self::record_stderr_line(&stderr_history, line);
+                                            }
+                                            Some(CommandEvent::Terminated(payload)) => {
+                                                Self::handle_termination(
+                                                    &handle,
+                                                    became_ready,
+                                                    &manually_killed,
+                                                    &has_exited,
+                                                    &last_error,
+                                                    payload,
+                                                )
+                                                .await;
+                                                break 'event_loop;
+                                            }
+                                            Some(_) => {}
+                                            None => break 'event_loop,
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                        CommandEvent::Stderr(bytes) => {
+                            let line = Self::log_line(&handle, ConsoleStream::Stderr, &bytes);
+                            Self::record_stderr_line(&stderr_history, line);
+                        }
+                        CommandEvent::Terminated(payload) => {
+                            Self::handle_termination(
+                                &handle,
+                                became_ready,
+                                &manually_killed,
+                                &has_exited,
+                                &last_error,
+                                payload,
+                            )
+                            .await;
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        });
+
+        self.child = Some(child);
+        Ok(())
+    }
+
+    // Logs and forwards one stdout/stderr line, returning the decoded line.
+    fn log_line(app_handle: &AppHandle, stream: ConsoleStream, bytes: &[u8]) -> String {
+        let line = String::from_utf8_lossy(bytes).trim_end().to_string();
+        let label = match stream {
+            ConsoleStream::Stdout => "stdout",
+            ConsoleStream::Stderr => "stderr",
+        };
+        eprintln!("[sidecar {label}] {line}");
+        emit_console_event(app_handle, stream, line.clone());
+        line
+    }
+
+    // Appends a stderr line to the bounded trailing-lines history.
+    fn record_stderr_line(history: &Mutex<VecDeque<String>>, line: String) {
+        let mut history = history.lock().unwrap();
+        if history.len() >= STDERR_HISTORY_CAP {
+            history.pop_front();
+        }
+        history.push_back(line);
+    }
+
+    // Common handling for an observed Terminated event, whether it arrives
+    // while idle or while a health check is in flight.
+    async fn handle_termination(
+        app_handle: &AppHandle,
+        became_ready: bool,
+        manually_killed: &AtomicBool,
+        has_exited: &AtomicBool,
+        last_error: &Mutex<Option<String>>,
+        payload: impl std::fmt::Debug,
+    ) {
+        eprintln!("Sidecar terminated: {:?}", payload);
+        has_exited.store(true, Ordering::SeqCst);
+
+        if !became_ready {
+            let mut error = last_error.lock().unwrap();
+            if error.is_none() {
+                *error = Some("Sidecar exited before becoming ready".to_string());
+            }
+        }
+
+        if !manually_killed.load(Ordering::SeqCst) {
+            Self::supervise_restart(app_handle).await;
+        }
+    }
+
+    // Marks the sidecar Running and publishes its port on a successful
+    // probe, or Crashed with the port cleared on failure. Returns whether
+    // the sidecar became ready.
+    fn finish_health_check(app_handle: &AppHandle, port: u16, result: Result<(), String>) -> bool {
+        let state = app_handle.state::<Mutex<SidecarState>>();
+        let lifecycle = app_handle.state::<Mutex<SidecarLifecycleService>>();
+
+        match result {
+            Ok(()) => {
+                state.lock().unwrap().port = Some(port);
+                lifecycle.lock().unwrap().status = SidecarStatus::Running;
+                eprintln!("Sidecar health check passed on port {port}");
+                true
+            }
+            Err(e) => {
+                state.lock().unwrap().port = None;
+                let mut lifecycle = lifecycle.lock().unwrap();
+                lifecycle.port = None;
+                *lifecycle.last_error.lock().unwrap() = Some(e.clone());
+                lifecycle.status = SidecarStatus::Crashed;
+                eprintln!("Sidecar health check failed: {e}");
+                false
+            }
+        }
+    }
+
+    // Blocking TCP-connect health probe, retried until `timeout` elapses.
+    fn probe_health(port: u16, timeout: Duration) -> Result<(), String> {
+        let addr = SocketAddr::from(([127, 0, 0, 1], port));
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            if TcpStream::connect_timeout(&addr, Duration::from_millis(500)).is_ok() {
+                return Ok(());
+            }
+            if Instant::now() >= deadline {
+                return Err(format!(
+                    "Health check on 127.0.0.1:{port} timed out after {timeout:?}"
+                ));
+            }
+            std::thread::sleep(HEALTH_CHECK_POLL_INTERVAL);
+        }
+    }
+
+    // Respawns the sidecar after an unexpected exit, retrying with capped
+    // exponential backoff and emitting a status event on each attempt.
+    async fn supervise_restart(app_handle: &AppHandle) {
+        {
+            let lifecycle = app_handle.state::<Mutex<SidecarLifecycleService>>();
+            lifecycle.lock().unwrap().status = SidecarStatus::Crashed;
+        }
+
+        let mut backoff = INITIAL_BACKOFF;
+        let mut attempt: u32 = 0;
+
+        loop {
+            attempt += 1;
+            emit_status_event(
+                app_handle,
+                SidecarStatus::Starting,
+                attempt,
+                format!("Sidecar crashed, reconnecting (attempt {attempt})..."),
+            );
+            tokio::time::sleep(backoff).await;
+
+            let lifecycle = app_handle.state::<Mutex<SidecarLifecycleService>>();
+            let mut lifecycle = lifecycle.lock().unwrap();
+
+            // A manual stop/restart raced in while we were backing off;
+            // let it own the lifecycle instead of fighting it. stop() sets
+            // manually_killed; a start() swaps in a fresh flag but moves the
+            // status off Crashed.
+            if lifecycle.manually_killed.load(Ordering::SeqCst)
+                || lifecycle.status != SidecarStatus::Crashed
+            {
+                return;
+            }
+
+            // Drop the dead child's handle, killing it first on the off
+            // chance it's still alive.
+            if let Some(stale_child) = lifecycle.child.take() {
+                let _ = stale_child.kill();
+            }
+            match lifecycle.start(app_handle) {
+                Ok(()) => return,
+                Err(e) => {
+                    eprintln!("Sidecar restart attempt {attempt} failed: {e}");
+                    lifecycle.status = SidecarStatus::Crashed;
+                    backoff = next_backoff(backoff);
+                }
+            }
+        }
+    }
+
+    // Stops the current child, if any, and resets port/status to Stopped
+    // unconditionally — even if the final kill() fails — since self.child
+    // is already gone either way.
+    pub fn stop(&mut self, app_handle: &AppHandle) -> Result<(), String> {
+        self.manually_killed.store(true, Ordering::SeqCst);
+
+        let kill_result = match self.child.take() {
+            Some(child) => self.graceful_shutdown(child),
+            None => Ok(()),
+        };
+
+        self.port = None;
+        self.status = SidecarStatus::Stopped;
+
+        let state = app_handle.state::<Mutex<SidecarState>>();
+        state.lock().map_err(|e| e.to_string())?.port = None;
+
+        kill_result
+    }
+
+    // Writes the shutdown sentinel and waits up to SHUTDOWN_GRACE_PERIOD
+    // for the event loop to observe the exit, falling back to kill().
+    fn graceful_shutdown(&self, mut child: CommandChild) -> Result<(), String> {
+        if let Err(e) = child.write(SHUTDOWN_SENTINEL) {
+            eprintln!("Failed to write shutdown sentinel to sidecar stdin: {e}");
+        }
+
+        let deadline = Instant::now() + SHUTDOWN_GRACE_PERIOD;
+        while Instant::now() < deadline {
+            if self.has_exited.load(Ordering::SeqCst) {
+                eprintln!("Sidecar exited gracefully after shutdown request");
+                return Ok(());
+            }
+            std::thread::sleep(Duration::from_millis(100));
+        }
+
+        eprintln!("Sidecar did not exit within the grace period, force killing");
+        child.kill().map_err(|e| format!("Failed to kill sidecar: {e}"))
+    }
+
+    // Stops the current sidecar, if running, and spawns a fresh one.
+    pub fn restart(&mut self, app_handle: &AppHandle) -> Result<(), String> {
+        // stop() already resets port/status regardless of whether the kill
+        // itself succeeded; log a failure instead of bailing out so a
+        // restart always spawns a replacement.
+        if let Err(e) = self.stop(app_handle) {
+            eprintln!("Sidecar restart: stop reported an error, starting anyway: {e}");
+        }
+        self.start(app_handle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_until_capped_at_max() {
+        let mut backoff = INITIAL_BACKOFF;
+        for _ in 0..3 {
+            backoff = next_backoff(backoff);
+        }
+        assert_eq!(backoff, Duration::from_secs(2));
+
+        for _ in 0..10 {
+            backoff = next_backoff(backoff);
+        }
+        assert_eq!(backoff, MAX_BACKOFF);
+    }
+
+    #[test]
+    fn stderr_history_evicts_oldest_past_cap() {
+        let history = Mutex::new(VecDeque::new());
+        for i in 0..STDERR_HISTORY_CAP + 5 {
+            SidecarLifecycleService::record_stderr_line(&history, format!("line {i}"));
+        }
+
+        let history = history.lock().unwrap();
+        assert_eq!(history.len(), STDERR_HISTORY_CAP);
+        assert_eq!(history.front().unwrap(), "line 5");
+        assert_eq!(history.back().unwrap(), &format!("line {}", STDERR_HISTORY_CAP + 4));
+    }
+
+    #[test]
+    fn last_error_message_includes_stderr_tail_when_present() {
+        let service = SidecarLifecycleService::new();
+        *service.last_error.lock().unwrap() = Some("boom".to_string());
+        service.stderr_history.lock().unwrap().push_back("stack trace".to_string());
+
+        let message = service.last_error_message();
+        assert!(message.starts_with("boom"));
+        assert!(message.contains("stack trace"));
+    }
+
+    #[test]
+    fn last_error_message_is_just_the_reason_without_stderr() {
+        let service = SidecarLifecycleService::new();
+        *service.last_error.lock().unwrap() = Some("boom".to_string());
+
+        assert_eq!(service.last_error_message(), "boom");
+    }
+}